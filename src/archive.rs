@@ -0,0 +1,358 @@
+//! In-memory scanning of archive containers (`.zip`, `.tar`, `.tar.gz`/`.tgz`,
+//! `.gz`, `.war`, `.jar`).
+//!
+//! Webshells are frequently smuggled onto a target inside an uploaded
+//! archive rather than dropped as a bare file, so the CLI can optionally
+//! reach inside these containers and scan their entries as if they were
+//! scanned directly. Results are reported under a composite path of the
+//! form `upload.zip!shell.php` so a hit can still be traced back to both
+//! the container and the entry that triggered it.
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use webshell_scanner::{FrameworkDetector, ScanContext, WebshellScanResult, WebshellScanner};
+
+/// Maximum depth of nested archives (a zip inside a zip inside a zip...)
+/// that will be followed before entries are skipped.
+const MAX_NESTING_DEPTH: usize = 5;
+
+/// Maximum size, in bytes, of a single decompressed entry, as a zip-bomb
+/// guard. Zip and tar entries larger than this are skipped rather than read;
+/// gzip data that decompresses to more than this fails the archive's scan
+/// with an error.
+const MAX_ENTRY_SIZE: u64 = 256 * 1024 * 1024;
+
+/// One scanned entry pulled out of an archive.
+pub struct ArchiveEntry {
+    /// Composite path, e.g. `upload.zip!shell.php` or `upload.zip!a.jar!cmd.jsp`.
+    pub path: String,
+    pub result: WebshellScanResult,
+    pub sha256: String,
+    pub fuzzy_hash: String,
+}
+
+/// The parts of an archive scan that stay the same across every recursive
+/// call, bundled up so they thread through as one argument instead of four.
+#[derive(Clone, Copy)]
+struct ArchiveScanCtx<'a> {
+    scanner: &'a WebshellScanner,
+    context_aware: bool,
+    framework_detector: Option<&'a FrameworkDetector>,
+    depth: usize,
+}
+
+impl<'a> ArchiveScanCtx<'a> {
+    /// Returns a copy of this context one nesting level deeper.
+    fn nested(&self) -> Self {
+        ArchiveScanCtx {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+}
+
+/// Returns `true` if `path`'s extension marks it as an archive container
+/// this module knows how to open.
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    name.ends_with(".zip")
+        || name.ends_with(".tar")
+        || name.ends_with(".tar.gz")
+        || name.ends_with(".tgz")
+        || name.ends_with(".gz")
+        || name.ends_with(".war")
+        || name.ends_with(".jar")
+}
+
+/// Scans every webshell-language entry inside the archive at `path`,
+/// recursing into nested archives up to [`MAX_NESTING_DEPTH`].
+pub fn scan_archive(
+    scanner: &WebshellScanner,
+    path: &Path,
+    context_aware: bool,
+    framework_detector: Option<&FrameworkDetector>,
+) -> Result<Vec<ArchiveEntry>> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let label = path.display().to_string();
+    let ctx = ArchiveScanCtx {
+        scanner,
+        context_aware,
+        framework_detector,
+        depth: 0,
+    };
+    let mut out = Vec::new();
+    scan_archive_bytes(ctx, &bytes, &label, &mut out)?;
+    Ok(out)
+}
+
+fn scan_archive_bytes(
+    ctx: ArchiveScanCtx,
+    bytes: &[u8],
+    label: &str,
+    out: &mut Vec<ArchiveEntry>,
+) -> Result<()> {
+    if ctx.depth >= MAX_NESTING_DEPTH {
+        return Ok(());
+    }
+
+    let lower = label.to_ascii_lowercase();
+    if lower.ends_with(".zip") || lower.ends_with(".war") || lower.ends_with(".jar") {
+        scan_zip(ctx, bytes, label, out)?;
+    } else if lower.ends_with(".tar") {
+        scan_tar(ctx, bytes, label, out)?;
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        let decompressed = gunzip(bytes)?;
+        scan_tar(ctx, &decompressed, label, out)?;
+    } else if lower.ends_with(".gz") {
+        let decompressed = gunzip(bytes)?;
+        let file_name = label.rsplit(['!', '/']).next().unwrap_or(label);
+        let inner_name = file_name.trim_end_matches(".gz");
+        scan_entry(ctx, &decompressed, label, inner_name, out)?;
+    }
+
+    Ok(())
+}
+
+fn scan_zip(ctx: ArchiveScanCtx, bytes: &[u8], label: &str, out: &mut Vec<ArchiveEntry>) -> Result<()> {
+    let reader = std::io::Cursor::new(bytes);
+    let mut zip = zip::ZipArchive::new(reader).with_context(|| format!("Failed to open {label} as zip"))?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if !entry.is_file() || entry.size() > MAX_ENTRY_SIZE {
+            continue;
+        }
+
+        let entry_name = entry.name().to_string();
+        let size_hint = entry.size() as usize;
+        let Some(buf) = read_bounded(&mut entry, size_hint)? else {
+            continue;
+        };
+
+        scan_entry(ctx, &buf, label, &entry_name, out)?;
+    }
+
+    Ok(())
+}
+
+fn scan_tar(ctx: ArchiveScanCtx, bytes: &[u8], label: &str, out: &mut Vec<ArchiveEntry>) -> Result<()> {
+    let mut archive = tar::Archive::new(std::io::Cursor::new(bytes));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() || entry.size() > MAX_ENTRY_SIZE {
+            continue;
+        }
+
+        let entry_name = entry.path()?.to_string_lossy().to_string();
+        let size_hint = entry.size() as usize;
+        let Some(buf) = read_bounded(&mut entry, size_hint)? else {
+            continue;
+        };
+
+        scan_entry(ctx, &buf, label, &entry_name, out)?;
+    }
+
+    Ok(())
+}
+
+fn scan_entry(
+    ctx: ArchiveScanCtx,
+    bytes: &[u8],
+    label: &str,
+    entry_name: &str,
+    out: &mut Vec<ArchiveEntry>,
+) -> Result<()> {
+    let composite_path = format!("{label}!{entry_name}");
+    let entry_path = Path::new(entry_name);
+
+    if is_archive(entry_path) {
+        return scan_archive_bytes(ctx.nested(), bytes, &composite_path, out);
+    }
+
+    let Some(language) = WebshellScanner::should_scan_language(entry_path) else {
+        return Ok(());
+    };
+
+    let Ok(content) = std::str::from_utf8(bytes) else {
+        return Ok(());
+    };
+
+    let (result, sha256) = if ctx.context_aware {
+        let context = ScanContext::from_path_with_detector(entry_path, ctx.framework_detector);
+        let result = ctx.scanner.scan_with_context(content, &context);
+        (result, webshell_scanner::sha256(bytes))
+    } else {
+        ctx.scanner.scan_with_hash(content, Some(language))
+    };
+
+    out.push(ArchiveEntry {
+        path: composite_path,
+        result,
+        sha256,
+        fuzzy_hash: WebshellScanner::fuzzy_hash(content),
+    });
+
+    Ok(())
+}
+
+/// Reads `reader` to the end, refusing to buffer more than
+/// [`MAX_ENTRY_SIZE`] bytes even if the container's own metadata claims a
+/// smaller size. Returns `Ok(None)` (skip, don't error) if the entry turns
+/// out to be oversized, matching the declared-size check callers already
+/// do up front.
+fn read_bounded(reader: &mut impl Read, size_hint: usize) -> Result<Option<Vec<u8>>> {
+    let mut buf = Vec::with_capacity(size_hint.min(MAX_ENTRY_SIZE as usize));
+    let read = reader
+        .take(MAX_ENTRY_SIZE + 1)
+        .read_to_end(&mut buf)
+        .context("Failed to read archive entry")?;
+
+    if read as u64 > MAX_ENTRY_SIZE {
+        return Ok(None);
+    }
+
+    Ok(Some(buf))
+}
+
+/// Decompresses `bytes`, refusing to produce more than [`MAX_ENTRY_SIZE`]
+/// bytes of output regardless of how small the compressed input is. This is
+/// what actually enforces the zip-bomb guard for `.gz`/`.tar.gz`/`.tgz`
+/// entries, since their compressed size on disk says nothing about how much
+/// memory decompressing them will take.
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    use flate2::read::GzDecoder;
+
+    let mut decoder = GzDecoder::new(bytes).take(MAX_ENTRY_SIZE + 1);
+    let mut out = Vec::new();
+    let read = decoder
+        .read_to_end(&mut out)
+        .context("Failed to decompress gzip data")?;
+
+    if read as u64 > MAX_ENTRY_SIZE {
+        bail!("gzip entry exceeds the {MAX_ENTRY_SIZE}-byte decompression cap");
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        for (name, contents) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn build_tar(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn scans_webshell_entry_inside_zip() {
+        let zip_bytes = build_zip(&[("shell.php", b"<?php eval($_GET['cmd']); ?>")]);
+        let scanner = WebshellScanner::new(50);
+        let ctx = ArchiveScanCtx {
+            scanner: &scanner,
+            context_aware: false,
+            framework_detector: None,
+            depth: 0,
+        };
+        let mut out = Vec::new();
+        scan_archive_bytes(ctx, &zip_bytes, "upload.zip", &mut out).unwrap();
+
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].path, "upload.zip!shell.php");
+        assert!(out[0].result.is_malicious);
+    }
+
+    #[test]
+    fn scans_webshell_entry_inside_tar() {
+        let tar_bytes = build_tar(&[("shell.php", b"<?php eval($_GET['cmd']); ?>")]);
+        let scanner = WebshellScanner::new(50);
+        let ctx = ArchiveScanCtx {
+            scanner: &scanner,
+            context_aware: false,
+            framework_detector: None,
+            depth: 0,
+        };
+        let mut out = Vec::new();
+        scan_archive_bytes(ctx, &tar_bytes, "upload.tar", &mut out).unwrap();
+
+        assert_eq!(out.len(), 1);
+        assert!(out[0].result.is_malicious);
+    }
+
+    #[test]
+    fn nesting_past_max_depth_is_skipped() {
+        let scanner = WebshellScanner::new(50);
+        let ctx = ArchiveScanCtx {
+            scanner: &scanner,
+            context_aware: false,
+            framework_detector: None,
+            depth: MAX_NESTING_DEPTH,
+        };
+        let zip_bytes = build_zip(&[("shell.php", b"<?php eval($_GET['cmd']); ?>")]);
+        let mut out = Vec::new();
+        scan_archive_bytes(ctx, &zip_bytes, "upload.zip", &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn read_bounded_rejects_data_over_the_cap() {
+        let oversized = vec![0u8; (MAX_ENTRY_SIZE + 10) as usize];
+        let mut reader = std::io::Cursor::new(oversized);
+        let result = read_bounded(&mut reader, 0).unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn read_bounded_accepts_data_under_the_cap() {
+        let small = vec![1u8, 2, 3];
+        let mut reader = std::io::Cursor::new(small.clone());
+        let result = read_bounded(&mut reader, small.len()).unwrap();
+        assert_eq!(result, Some(small));
+    }
+
+    #[test]
+    fn gunzip_rejects_output_over_the_cap() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let oversized = vec![0u8; (MAX_ENTRY_SIZE + 1024) as usize];
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(&oversized).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert!(gunzip(&compressed).is_err());
+    }
+
+    #[test]
+    fn gunzip_roundtrips_small_data() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        encoder.write_all(b"hello world").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(gunzip(&compressed).unwrap(), b"hello world");
+    }
+}
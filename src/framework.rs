@@ -0,0 +1,60 @@
+//! Context-aware scanning support.
+//!
+//! Webshell patterns occasionally show up legitimately inside a known
+//! framework's own source (e.g. a templating engine's `eval`-like helper).
+//! [`FrameworkDetector`] recognizes a handful of common PHP/JSP/Python
+//! frameworks from a file's path so [`ScanContext`]-aware scanning
+//! can dampen detections that are expected noise there.
+
+use std::path::Path;
+
+/// A recognized web framework or CMS.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Framework {
+    WordPress,
+    Laravel,
+    Django,
+    Spring,
+}
+
+impl Framework {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Framework::WordPress => "WordPress",
+            Framework::Laravel => "Laravel",
+            Framework::Django => "Django",
+            Framework::Spring => "Spring",
+        }
+    }
+}
+
+/// Detects which framework, if any, a path belongs to by looking for
+/// telltale directory segments (`wp-content`, `vendor/laravel`, ...).
+pub struct FrameworkDetector;
+
+impl FrameworkDetector {
+    pub fn new() -> Self {
+        FrameworkDetector
+    }
+
+    pub fn detect(&self, path: &Path) -> Option<Framework> {
+        let path_str = path.to_string_lossy();
+        if path_str.contains("wp-content") || path_str.contains("wp-includes") {
+            Some(Framework::WordPress)
+        } else if path_str.contains("vendor/laravel") || path_str.contains("artisan") {
+            Some(Framework::Laravel)
+        } else if path_str.contains("django") || path_str.contains("manage.py") {
+            Some(Framework::Django)
+        } else if path_str.contains("WEB-INF") {
+            Some(Framework::Spring)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for FrameworkDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
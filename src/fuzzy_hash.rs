@@ -0,0 +1,237 @@
+//! Context-triggered piecewise (ssdeep-style) fuzzy hashing.
+//!
+//! A SHA-256 match only catches byte-for-byte duplicates, which misses
+//! webshells that have been trivially re-packed (renamed variables, added
+//! whitespace, re-ordered functions). A fuzzy hash instead breaks the file
+//! into content-defined chunks and hashes each chunk, so two variants of
+//! the same shell family (c99, b374k, ...) produce similar signatures
+//! even though their bytes differ.
+//!
+//! The signature format is `block_size:hash1:hash2`, where `hash1` is
+//! computed with `block_size` and `hash2` with `2 * block_size` — mirroring
+//! ssdeep's own format so two signatures can be compared at matching or
+//! adjacent block sizes.
+
+use crate::scanner::WebshellScanner;
+
+const BASE64_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const ROLLING_WINDOW: usize = 7;
+const MIN_BLOCK_SIZE: u64 = 4;
+const TARGET_SIGNATURE_LEN: usize = 64;
+
+struct RollingHash {
+    window: [u8; ROLLING_WINDOW],
+    pos: usize,
+    sum: u32,
+}
+
+impl RollingHash {
+    fn new() -> Self {
+        RollingHash {
+            window: [0; ROLLING_WINDOW],
+            pos: 0,
+            sum: 0,
+        }
+    }
+
+    /// Slides `byte` into the window, returning the updated rolling sum.
+    fn roll(&mut self, byte: u8) -> u32 {
+        self.sum = self
+            .sum
+            .wrapping_add(byte as u32)
+            .wrapping_sub(self.window[self.pos] as u32);
+        self.window[self.pos] = byte;
+        self.pos = (self.pos + 1) % ROLLING_WINDOW;
+        self.sum
+    }
+}
+
+/// FNV-1a accumulator used to hash the bytes within one piece.
+struct PieceHash(u32);
+
+impl PieceHash {
+    const OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const PRIME: u32 = 0x0100_0193;
+
+    fn new() -> Self {
+        PieceHash(Self::OFFSET_BASIS)
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.0 = (self.0 ^ byte as u32).wrapping_mul(Self::PRIME);
+    }
+
+    /// Maps the accumulated hash to one base64 signature character and
+    /// resets the accumulator for the next piece.
+    fn take_char(&mut self) -> char {
+        let c = BASE64_ALPHABET[(self.0 as usize) & 0x3f] as char;
+        *self = PieceHash::new();
+        c
+    }
+}
+
+/// Computes one piecewise hash of `data` for a given `block_size`: a
+/// rolling hash is swept over the bytes, and a signature character is
+/// emitted from the current piece's FNV hash whenever
+/// `rolling_hash % block_size == block_size - 1`.
+fn piecewise_hash(data: &[u8], block_size: u64) -> String {
+    let mut rolling = RollingHash::new();
+    let mut piece = PieceHash::new();
+    let mut signature = String::new();
+
+    for &byte in data {
+        piece.push(byte);
+        let r = rolling.roll(byte) as u64;
+        if block_size > 0 && r % block_size == block_size - 1 {
+            signature.push(piece.take_char());
+        }
+    }
+
+    if piece.0 != PieceHash::OFFSET_BASIS {
+        signature.push(piece.take_char());
+    }
+
+    signature
+}
+
+/// Picks the starting block size the way ssdeep does: the smallest power
+/// of two such that `data.len() / block_size` is roughly the target
+/// signature length.
+fn initial_block_size(len: usize) -> u64 {
+    let mut block_size = MIN_BLOCK_SIZE;
+    while (len as u64) / block_size > TARGET_SIGNATURE_LEN as u64 {
+        block_size *= 2;
+    }
+    block_size
+}
+
+impl WebshellScanner {
+    /// Computes a context-triggered piecewise hash signature of `content`,
+    /// useful for clustering near-duplicate webshell variants that a plain
+    /// SHA-256 comparison would miss.
+    pub fn fuzzy_hash(content: &str) -> String {
+        let data = content.as_bytes();
+        let mut block_size = initial_block_size(data.len());
+
+        let hash1 = loop {
+            let hash1 = piecewise_hash(data, block_size);
+            if hash1.len() > TARGET_SIGNATURE_LEN && block_size > MIN_BLOCK_SIZE {
+                block_size /= 2;
+                continue;
+            }
+            break hash1;
+        };
+
+        let hash2 = piecewise_hash(data, block_size * 2);
+        format!("{block_size}:{hash1}:{hash2}")
+    }
+}
+
+/// Compares two fuzzy-hash signatures and returns a similarity score from
+/// 0 (unrelated) to 100 (identical), or 0 if their block sizes are too far
+/// apart to be meaningfully compared.
+pub fn fuzzy_compare(a: &str, b: &str) -> u8 {
+    let Some((bs_a, h1_a, h2_a)) = parse_signature(a) else {
+        return 0;
+    };
+    let Some((bs_b, h1_b, h2_b)) = parse_signature(b) else {
+        return 0;
+    };
+
+    if bs_a == bs_b {
+        similarity(h1_a, h1_b)
+    } else if bs_a == bs_b * 2 {
+        similarity(h1_a, h2_b)
+    } else if bs_b == bs_a * 2 {
+        similarity(h2_a, h1_b)
+    } else {
+        0
+    }
+}
+
+fn parse_signature(signature: &str) -> Option<(u64, &str, &str)> {
+    let mut parts = signature.splitn(3, ':');
+    let block_size = parts.next()?.parse().ok()?;
+    let hash1 = parts.next()?;
+    let hash2 = parts.next()?;
+    Some((block_size, hash1, hash2))
+}
+
+fn similarity(a: &str, b: &str) -> u8 {
+    if a.is_empty() && b.is_empty() {
+        return 100;
+    }
+
+    let distance = edit_distance(a, b);
+    let max_len = a.len().max(b.len());
+    let score = 100 - (distance * 100 / max_len);
+    score as u8
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut prev = row[0];
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cur = row[j];
+            row[j] = if a[i - 1] == b[j - 1] {
+                prev
+            } else {
+                1 + prev.min(row[j]).min(row[j - 1])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_hash_is_deterministic() {
+        let content = "some <?php eval($_GET['cmd']); ?> content repeated a bit more";
+        assert_eq!(
+            WebshellScanner::fuzzy_hash(content),
+            WebshellScanner::fuzzy_hash(content)
+        );
+    }
+
+    #[test]
+    fn fuzzy_hash_has_expected_format() {
+        let signature = WebshellScanner::fuzzy_hash("some sample content");
+        let parts: Vec<&str> = signature.splitn(3, ':').collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].parse::<u64>().is_ok());
+    }
+
+    #[test]
+    fn fuzzy_compare_identical_signatures_is_100() {
+        let signature = WebshellScanner::fuzzy_hash("identical content for comparison");
+        assert_eq!(fuzzy_compare(&signature, &signature), 100);
+    }
+
+    #[test]
+    fn fuzzy_compare_unrelated_content_scores_lower() {
+        let a = WebshellScanner::fuzzy_hash("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+        let b = WebshellScanner::fuzzy_hash("zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz");
+        assert!(fuzzy_compare(&a, &a) > fuzzy_compare(&a, &b));
+    }
+
+    #[test]
+    fn fuzzy_compare_rejects_malformed_signatures() {
+        assert_eq!(fuzzy_compare("not-a-signature", "also-not-one"), 0);
+    }
+
+    #[test]
+    fn fuzzy_compare_handles_adjacent_block_sizes() {
+        assert_eq!(fuzzy_compare("3:abc:abcdef", "6:abcdef:xyz"), 100);
+    }
+}
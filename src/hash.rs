@@ -0,0 +1,60 @@
+//! SHA-256 hashing of scanned content.
+//!
+//! Pattern-based detection is heuristic; hashing lets a scan be checked
+//! against curated webshell hash feeds (IOC lists) with zero false
+//! positives, and lets embedders dedupe identical shells across a corpus.
+
+use sha2::{Digest, Sha256};
+
+use crate::scanner::{WebshellLanguage, WebshellScanResult, WebshellScanner};
+
+/// Returns the lowercase hex-encoded SHA-256 digest of `bytes`.
+pub fn sha256(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+impl WebshellScanner {
+    /// Scans `content` and returns the result alongside the SHA-256 of its
+    /// bytes, so callers doing IOC-hash or dedupe work don't need to hash
+    /// the file twice.
+    pub fn scan_with_hash(
+        &self,
+        content: &str,
+        language: Option<WebshellLanguage>,
+    ) -> (WebshellScanResult, String) {
+        let result = match language {
+            Some(lang) => self.scan_language(content, lang),
+            None => self.scan(content),
+        };
+        (result, sha256(content.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_matches_known_vector() {
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn sha256_of_empty_input() {
+        assert_eq!(
+            sha256(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn scan_with_hash_returns_matching_digest() {
+        let scanner = WebshellScanner::new(50);
+        let (_, digest) = scanner.scan_with_hash("abc", None);
+        assert_eq!(digest, sha256(b"abc"));
+    }
+}
@@ -0,0 +1,116 @@
+//! Scanning a suspected live webshell endpoint, or a raw file URL, without
+//! downloading it by hand first.
+//!
+//! This sits alongside the filesystem and stdin inputs: an `http://` or
+//! `https://` target is fetched with `reqwest` and its response body is run
+//! through the same scanning path as a file on disk.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use webshell_scanner::{WebshellLanguage, WebshellScanResult, WebshellScanner};
+
+/// Returns `true` if `target` looks like an `http://`/`https://` URL rather
+/// than a filesystem path.
+pub fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+/// Guesses the [`WebshellLanguage`] of a URL from its path extension, the
+/// same way [`WebshellScanner::should_scan_language`] does for files.
+pub fn language_from_url(url: &str) -> Option<WebshellLanguage> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    WebshellScanner::should_scan_language(std::path::Path::new(path))
+}
+
+/// The outcome of fetching and scanning one URL.
+pub struct UrlScanResult {
+    pub result: WebshellScanResult,
+    pub sha256: String,
+    pub fuzzy_hash: String,
+}
+
+/// Fetches `url`'s body and scans it as `language` (or, if `None`, as the
+/// language inferred from the URL's extension, falling back to only
+/// language-agnostic detections when none is recognized). Network failures are returned as
+/// `Err` so the caller can log a warning and keep scanning the remaining
+/// targets instead of aborting the run.
+pub fn scan_url(
+    scanner: &WebshellScanner,
+    url: &str,
+    timeout: Duration,
+    headers: &[(String, String)],
+    user_agent: Option<&str>,
+    language: Option<WebshellLanguage>,
+) -> Result<UrlScanResult> {
+    let mut builder = reqwest::blocking::Client::builder().timeout(timeout);
+    if let Some(user_agent) = user_agent {
+        builder = builder.user_agent(user_agent.to_string());
+    }
+    let client = builder.build().context("Failed to build HTTP client")?;
+
+    let mut request = client.get(url);
+    for (key, value) in headers {
+        request = request.header(key, value);
+    }
+
+    let response = request
+        .send()
+        .with_context(|| format!("Failed to fetch {url}"))?
+        .error_for_status()
+        .with_context(|| format!("{url} returned an error status"))?;
+
+    let body = response
+        .text()
+        .with_context(|| format!("Failed to read response body from {url}"))?;
+
+    let language = language.or_else(|| language_from_url(url));
+    let (result, sha256) = scanner.scan_with_hash(&body, language);
+    let fuzzy_hash = WebshellScanner::fuzzy_hash(&body);
+
+    Ok(UrlScanResult {
+        result,
+        sha256,
+        fuzzy_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_url_recognizes_http_and_https() {
+        assert!(is_url("http://example.com/shell.php"));
+        assert!(is_url("https://example.com/shell.php"));
+    }
+
+    #[test]
+    fn is_url_rejects_filesystem_paths() {
+        assert!(!is_url("/var/www/shell.php"));
+        assert!(!is_url("shell.php"));
+        assert!(!is_url("ftp://example.com/shell.php"));
+    }
+
+    #[test]
+    fn language_from_url_uses_the_path_extension() {
+        assert_eq!(
+            language_from_url("https://example.com/shell.php"),
+            Some(WebshellLanguage::Php)
+        );
+    }
+
+    #[test]
+    fn language_from_url_ignores_query_and_fragment() {
+        assert_eq!(
+            language_from_url("https://example.com/shell.php?a=1#frag"),
+            Some(WebshellLanguage::Php)
+        );
+    }
+
+    #[test]
+    fn language_from_url_returns_none_for_unknown_extension() {
+        assert_eq!(language_from_url("https://example.com/index.html"), None);
+    }
+}
@@ -31,9 +31,15 @@
 //! ```
 
 mod framework;
+mod fuzzy_hash;
+mod hash;
+mod rules;
 mod scanner;
 
 pub use framework::{Framework, FrameworkDetector};
+pub use fuzzy_hash::fuzzy_compare;
+pub use hash::sha256;
+pub use rules::{Rule, RuleSet};
 pub use scanner::{
     is_likely_minified, Detection, DetectionCategory, ScanContext, ThreatLevel, WebshellLanguage,
     WebshellScanResult, WebshellScanner,
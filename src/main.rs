@@ -5,12 +5,24 @@
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
 use colored::Colorize;
+use rayon::prelude::*;
+use std::collections::HashSet;
 use std::fs;
 use std::io::{self, Read};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use walkdir::WalkDir;
 
-use webshell_scanner::{FrameworkDetector, ScanContext, WebshellScanner};
+use webshell_scanner::{
+    fuzzy_compare, Detection, DetectionCategory, FrameworkDetector, RuleSet, ScanContext,
+    ThreatLevel, WebshellScanResult, WebshellScanner,
+};
+
+mod archive;
+mod http;
+mod state;
 
 #[derive(Parser)]
 #[command(name = "webshell-scanner")]
@@ -53,6 +65,51 @@ struct Cli {
     /// Only show malicious files (exit code 1 if any found)
     #[arg(short, long)]
     quiet: bool,
+
+    /// YAML file of custom rules to merge with the built-in detections
+    #[arg(long)]
+    rules: Option<PathBuf>,
+
+    /// Number of worker threads to scan with (default: available parallelism)
+    #[arg(short = 'j', long)]
+    jobs: Option<usize>,
+
+    /// Look inside .zip/.tar/.tar.gz/.tgz/.gz/.war/.jar archives and scan their entries
+    #[arg(long)]
+    scan_archives: bool,
+
+    /// Newline-delimited file of known-malicious SHA-256 hashes (IOC feed)
+    #[arg(long = "ioc-hashes")]
+    ioc_hashes: Option<PathBuf>,
+
+    /// Report candidate files whose fuzzy hash is similar to this signature, instead of scanning for detections
+    #[arg(long)]
+    similar_to: Option<String>,
+
+    /// Minimum similarity score (0-100) to report when using --similar-to
+    #[arg(long, default_value = "70")]
+    similarity_threshold: u8,
+
+    /// Timeout, in seconds, for http(s):// targets
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// Extra header to send with http(s):// requests, as "Name: Value" (repeatable)
+    #[arg(long = "header")]
+    headers: Vec<String>,
+
+    /// User-Agent to send with http(s):// requests
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Resume from (and update) a scan state file, skipping files whose
+    /// mtime and size haven't changed since they were last scanned
+    #[arg(long)]
+    resume: Option<PathBuf>,
+
+    /// With --resume, ignore the cached state and rescan every file anyway
+    #[arg(long, requires = "resume")]
+    force: bool,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
@@ -81,17 +138,19 @@ impl From<Language> for webshell_scanner::WebshellLanguage {
     }
 }
 
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct ScanOutput {
     path: String,
     is_malicious: bool,
     threat_level: String,
     language: Option<String>,
     obfuscation_score: u32,
+    sha256: String,
+    fuzzy_hash: String,
     detections: Vec<DetectionOutput>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct DetectionOutput {
     category: String,
     description: String,
@@ -102,13 +161,37 @@ struct DetectionOutput {
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
-    let scanner = WebshellScanner::new(cli.threshold);
+    let scanner = match &cli.rules {
+        Some(path) => {
+            let rule_set = RuleSet::from_file(path)
+                .with_context(|| format!("Failed to load rule pack {:?}", path))?;
+            WebshellScanner::with_rules(cli.threshold, rule_set)
+                .with_context(|| format!("Failed to compile rules from {:?}", path))?
+        }
+        None => WebshellScanner::new(cli.threshold),
+    };
     let framework_detector = if cli.context_aware {
         Some(FrameworkDetector::new())
     } else {
         None
     };
 
+    let ioc_hashes = match &cli.ioc_hashes {
+        Some(path) => Some(load_ioc_hashes(path)?),
+        None => None,
+    };
+
+    let resume = state::ResumeState::load(cli.resume.as_deref(), cli.force)?;
+
+    let jobs = cli
+        .jobs
+        .or_else(|| std::thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .context("Failed to build scan thread pool")?;
+
     let mut results = Vec::new();
     let mut malicious_count = 0;
 
@@ -119,52 +202,66 @@ fn main() -> Result<()> {
             .read_to_string(&mut content)
             .context("Failed to read from stdin")?;
 
-        let result = if let Some(lang) = cli.language {
-            scanner.scan_language(&content, lang.into())
-        } else {
-            scanner.scan(&content)
-        };
+        let (mut result, sha256) = scanner.scan_with_hash(&content, cli.language.map(Into::into));
+        apply_ioc_hashes(&mut result, &sha256, ioc_hashes.as_ref());
+        let fuzzy_hash = WebshellScanner::fuzzy_hash(&content);
 
         if result.is_malicious {
             malicious_count += 1;
         }
 
-        let output = ScanOutput {
-            path: "<stdin>".to_string(),
-            is_malicious: result.is_malicious,
-            threat_level: format!("{:?}", result.threat_level),
-            language: result.language.map(|l| l.name().to_string()),
-            obfuscation_score: result.obfuscation_score,
-            detections: result
-                .detections
-                .into_iter()
-                .map(|d| DetectionOutput {
-                    category: d.category.name().to_string(),
-                    description: d.description,
-                    pattern: truncate_pattern(&d.pattern, 100),
-                    line: d.line_number,
-                })
-                .collect(),
-        };
-
-        results.push(output);
+        results.push(result_to_output(
+            "<stdin>".to_string(),
+            result,
+            sha256,
+            fuzzy_hash,
+        ));
     } else {
-        // Scan files/directories
-        for path in &cli.paths {
-            if path.is_file() {
-                if let Some(output) = scan_file(
-                    &scanner,
-                    path,
-                    cli.context_aware,
-                    framework_detector.as_ref(),
-                )? {
-                    if output.is_malicious {
+        // http(s):// targets are fetched and scanned up front; everything
+        // else is a filesystem path and feeds the parallel walk below.
+        let url_targets: Vec<&str> = cli
+            .paths
+            .iter()
+            .filter_map(|p| p.to_str())
+            .filter(|p| http::is_url(p))
+            .collect();
+
+        let request_headers = parse_headers(&cli.headers)?;
+        for url in &url_targets {
+            match http::scan_url(
+                &scanner,
+                url,
+                Duration::from_secs(cli.timeout),
+                &request_headers,
+                cli.user_agent.as_deref(),
+                cli.language.map(Into::into),
+            ) {
+                Ok(scan) => {
+                    let mut result = scan.result;
+                    apply_ioc_hashes(&mut result, &scan.sha256, ioc_hashes.as_ref());
+                    if result.is_malicious {
                         malicious_count += 1;
                     }
+                    let output =
+                        result_to_output(url.to_string(), result, scan.sha256, scan.fuzzy_hash);
                     if output.is_malicious || cli.show_clean {
                         results.push(output);
                     }
                 }
+                Err(err) => {
+                    eprintln!("{} {:#}", "warning:".yellow().bold(), err);
+                }
+            }
+        }
+
+        // Collect every candidate path up front so scanning itself can be
+        // data-parallel instead of walking and scanning one file at a time.
+        let mut candidates = Vec::new();
+        for path in &cli.paths {
+            if path.to_str().map(http::is_url).unwrap_or(false) {
+                continue;
+            } else if path.is_file() {
+                candidates.push(path.clone());
             } else if path.is_dir() {
                 let walker = if cli.recursive {
                     WalkDir::new(path)
@@ -174,21 +271,74 @@ fn main() -> Result<()> {
 
                 for entry in walker.into_iter().filter_map(|e| e.ok()) {
                     if entry.file_type().is_file() {
-                        if let Some(output) = scan_file(
+                        candidates.push(entry.into_path());
+                    }
+                }
+            }
+        }
+
+        if let Some(target_signature) = &cli.similar_to {
+            return report_similar_files(&candidates, target_signature, cli.similarity_threshold, &pool, cli.quiet);
+        }
+
+        let scanner = Arc::new(scanner);
+        let framework_detector = Arc::new(framework_detector);
+        let malicious_total = AtomicUsize::new(0);
+
+        let mut scanned: Vec<ScanOutput> = pool.install(|| {
+            candidates
+                .par_iter()
+                .flat_map_iter(|path| {
+                    let outputs = if cli.scan_archives && archive::is_archive(path) {
+                        scan_archive_file(
                             &scanner,
-                            entry.path(),
+                            path,
                             cli.context_aware,
-                            framework_detector.as_ref(),
-                        )? {
-                            if output.is_malicious {
-                                malicious_count += 1;
-                            }
-                            if output.is_malicious || cli.show_clean {
-                                results.push(output);
+                            framework_detector.as_ref().as_ref(),
+                            ioc_hashes.as_ref(),
+                        )
+                    } else {
+                        match scan_file(
+                            &scanner,
+                            path,
+                            cli.context_aware,
+                            framework_detector.as_ref().as_ref(),
+                            ioc_hashes.as_ref(),
+                            resume.as_ref(),
+                        ) {
+                            Ok(Some(output)) => vec![output],
+                            Ok(None) => Vec::new(),
+                            Err(err) => {
+                                eprintln!("{} {:#}", "warning:".yellow().bold(), err);
+                                Vec::new()
                             }
                         }
-                    }
-                }
+                    };
+
+                    outputs.into_iter().filter_map(|output| {
+                        if output.is_malicious {
+                            malicious_total.fetch_add(1, Ordering::Relaxed);
+                        }
+                        (output.is_malicious || cli.show_clean).then_some(output)
+                    })
+                })
+                .collect()
+        });
+
+        scanned.sort_by(|a, b| a.path.cmp(&b.path));
+        malicious_count += malicious_total.into_inner();
+        results.extend(scanned);
+
+        if let (Some(resume), Some(resume_path)) = (resume, &cli.resume) {
+            let cache_hits = resume.cache_hits();
+            resume
+                .save(resume_path)
+                .with_context(|| format!("Failed to write resume state {:?}", resume_path))?;
+            if !cli.quiet && cache_hits > 0 {
+                eprintln!(
+                    "{}",
+                    format!("Reused {} cached result(s) from {:?}", cache_hits, resume_path).dimmed()
+                );
             }
         }
     }
@@ -238,29 +388,239 @@ fn scan_file(
     path: &std::path::Path,
     context_aware: bool,
     framework_detector: Option<&FrameworkDetector>,
+    ioc_hashes: Option<&HashSet<String>>,
+    resume: Option<&state::ResumeState>,
 ) -> Result<Option<ScanOutput>> {
     // Check if we should scan this file
     if WebshellScanner::should_scan_language(path).is_none() {
         return Ok(None);
     }
 
+    let Some(resume) = resume else {
+        let mut output = scan_file_uncached(scanner, path, context_aware, framework_detector)?;
+        apply_ioc_hashes_to_output(&mut output, ioc_hashes);
+        return Ok(Some(output));
+    };
+
+    match resume.check(path)? {
+        state::CheckOutcome::Hit(mut output) => {
+            apply_ioc_hashes_to_output(&mut output, ioc_hashes);
+            Ok(Some(output))
+        }
+        state::CheckOutcome::Miss { mtime, size } => {
+            // Cache the IOC-agnostic base result, not the IOC-flagged one: a
+            // later run's `--ioc-hashes` feed may add or drop this file's
+            // hash, and re-deriving the flag from the cached sha256 every
+            // time (instead of baking in whatever feed produced the cache
+            // entry) keeps a resumed scan in sync with the current feed.
+            let base_output =
+                scan_file_uncached(scanner, path, context_aware, framework_detector)?;
+            resume.record(path.display().to_string(), mtime, size, base_output.clone());
+            let mut output = base_output;
+            apply_ioc_hashes_to_output(&mut output, ioc_hashes);
+            Ok(Some(output))
+        }
+    }
+}
+
+fn scan_file_uncached(
+    scanner: &WebshellScanner,
+    path: &std::path::Path,
+    context_aware: bool,
+    framework_detector: Option<&FrameworkDetector>,
+) -> Result<ScanOutput> {
     let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
 
-    let result = if context_aware {
+    let (result, sha256) = if context_aware {
         let context = ScanContext::from_path_with_detector(path, framework_detector);
-        scanner.scan_with_context(&content, &context)
-    } else if let Some(lang) = WebshellScanner::should_scan_language(path) {
-        scanner.scan_language(&content, lang)
+        let result = scanner.scan_with_context(&content, &context);
+        (result, webshell_scanner::sha256(content.as_bytes()))
     } else {
-        scanner.scan(&content)
+        scanner.scan_with_hash(&content, WebshellScanner::should_scan_language(path))
+    };
+    let fuzzy_hash = WebshellScanner::fuzzy_hash(&content);
+
+    Ok(result_to_output(
+        path.display().to_string(),
+        result,
+        sha256,
+        fuzzy_hash,
+    ))
+}
+
+/// Computes each candidate's fuzzy hash and reports those whose similarity
+/// to `target_signature` meets `threshold`, instead of running the usual
+/// detection scan. Used by `--similar-to` to cluster webshell variants.
+fn report_similar_files(
+    candidates: &[PathBuf],
+    target_signature: &str,
+    threshold: u8,
+    pool: &rayon::ThreadPool,
+    quiet: bool,
+) -> Result<()> {
+    #[derive(serde::Serialize)]
+    struct SimilarOutput {
+        path: String,
+        similarity: u8,
+    }
+
+    let mut hits: Vec<SimilarOutput> = pool.install(|| {
+        candidates
+            .par_iter()
+            .filter_map(|path| {
+                let content = fs::read_to_string(path).ok()?;
+                let signature = WebshellScanner::fuzzy_hash(&content);
+                let similarity = fuzzy_compare(&signature, target_signature);
+                (similarity >= threshold).then_some(SimilarOutput {
+                    path: path.display().to_string(),
+                    similarity,
+                })
+            })
+            .collect()
+    });
+    hits.sort_by(|a, b| b.similarity.cmp(&a.similarity).then_with(|| a.path.cmp(&b.path)));
+
+    if !quiet {
+        for hit in &hits {
+            println!(
+                "{} {} (similarity {})",
+                "SIMILAR".yellow().bold(),
+                hit.path,
+                hit.similarity
+            );
+        }
+        eprintln!();
+        eprintln!(
+            "{}",
+            format!("Found {} similar file(s)", hits.len()).cyan()
+        );
+    }
+
+    if !hits.is_empty() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Scans every webshell-language entry found inside an archive container,
+/// reporting each hit under a composite `container!entry` path. Archive
+/// errors (corrupt zip, truncated tar, ...) are logged as warnings rather
+/// than aborting the run, matching the per-file error handling used for
+/// plain files.
+fn scan_archive_file(
+    scanner: &WebshellScanner,
+    path: &std::path::Path,
+    context_aware: bool,
+    framework_detector: Option<&FrameworkDetector>,
+    ioc_hashes: Option<&HashSet<String>>,
+) -> Vec<ScanOutput> {
+    match archive::scan_archive(scanner, path, context_aware, framework_detector) {
+        Ok(entries) => entries
+            .into_iter()
+            .map(|mut entry| {
+                apply_ioc_hashes(&mut entry.result, &entry.sha256, ioc_hashes);
+                result_to_output(entry.path, entry.result, entry.sha256, entry.fuzzy_hash)
+            })
+            .collect(),
+        Err(err) => {
+            eprintln!("{} {:#}", "warning:".yellow().bold(), err);
+            Vec::new()
+        }
+    }
+}
+
+/// Parses `--header "Name: Value"` arguments into `(name, value)` pairs.
+fn parse_headers(raw: &[String]) -> Result<Vec<(String, String)>> {
+    raw.iter()
+        .map(|header| {
+            let (name, value) = header
+                .split_once(':')
+                .with_context(|| format!("Invalid --header {header:?}, expected \"Name: Value\""))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Loads a newline-delimited list of known-malicious SHA-256 hashes,
+/// ignoring blank lines and `#`-prefixed comments.
+fn load_ioc_hashes(path: &std::path::Path) -> Result<HashSet<String>> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.to_ascii_lowercase())
+        .collect())
+}
+
+/// Forces `result` to `Malicious` with a [`DetectionCategory::KnownHash`]
+/// detection when `sha256` appears in `ioc_hashes`, regardless of what the
+/// pattern-based detections found.
+fn apply_ioc_hashes(result: &mut WebshellScanResult, sha256: &str, ioc_hashes: Option<&HashSet<String>>) {
+    let Some(ioc_hashes) = ioc_hashes else {
+        return;
+    };
+    if !ioc_hashes.contains(sha256) {
+        return;
+    }
+
+    result.is_malicious = true;
+    result.threat_level = ThreatLevel::Malicious;
+    result.detections.push(Detection {
+        id: "known-hash-ioc".to_string(),
+        category: DetectionCategory::KnownHash,
+        description: "File hash matches a known-malicious IOC".to_string(),
+        pattern: sha256.to_string(),
+        line_number: None,
+        severity: 100,
+    });
+}
+
+/// Applies the IOC-hash check to a [`ScanOutput`], whether freshly scanned
+/// or reused from the resume cache. [`state::ResumeState`] caches the
+/// IOC-agnostic base result, so this always re-derives the flag from the
+/// current `--ioc-hashes` feed instead of trusting a verdict baked in by
+/// whatever feed was active on a previous run.
+fn apply_ioc_hashes_to_output(output: &mut ScanOutput, ioc_hashes: Option<&HashSet<String>>) {
+    let Some(ioc_hashes) = ioc_hashes else {
+        return;
     };
+    if !ioc_hashes.contains(&output.sha256) {
+        return;
+    }
+    if output
+        .detections
+        .iter()
+        .any(|d| d.category == DetectionCategory::KnownHash.name())
+    {
+        return;
+    }
+
+    output.is_malicious = true;
+    output.threat_level = format!("{:?}", ThreatLevel::Malicious);
+    output.detections.push(DetectionOutput {
+        category: DetectionCategory::KnownHash.name().to_string(),
+        description: "File hash matches a known-malicious IOC".to_string(),
+        pattern: output.sha256.clone(),
+        line: None,
+    });
+}
 
-    Ok(Some(ScanOutput {
-        path: path.display().to_string(),
+fn result_to_output(
+    path: String,
+    result: WebshellScanResult,
+    sha256: String,
+    fuzzy_hash: String,
+) -> ScanOutput {
+    ScanOutput {
+        path,
         is_malicious: result.is_malicious,
         threat_level: format!("{:?}", result.threat_level),
         language: result.language.map(|l| l.name().to_string()),
         obfuscation_score: result.obfuscation_score,
+        sha256,
+        fuzzy_hash,
         detections: result
             .detections
             .into_iter()
@@ -271,7 +631,7 @@ fn scan_file(
                 line: d.line_number,
             })
             .collect(),
-    }))
+    }
 }
 
 fn print_text_result(result: &ScanOutput) {
@@ -322,3 +682,168 @@ fn truncate_pattern(pattern: &str, max_len: usize) -> String {
         pattern.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unique_temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("webshell-scanner-test-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn scan_file_skips_unrecognized_extension() {
+        let path = unique_temp_path("skip.txt");
+        fs::write(&path, "just some text").unwrap();
+        let scanner = WebshellScanner::new(50);
+
+        let result = scan_file(&scanner, &path, false, None, None, None);
+
+        fs::remove_file(&path).ok();
+        assert!(result.unwrap().is_none());
+    }
+
+    #[test]
+    fn scan_file_errors_on_missing_path() {
+        let path = unique_temp_path("does-not-exist.php");
+        let scanner = WebshellScanner::new(50);
+
+        assert!(scan_file(&scanner, &path, false, None, None, None).is_err());
+    }
+
+    #[test]
+    fn scan_file_reports_malicious_content() {
+        let path = unique_temp_path("shell.php");
+        fs::write(&path, "<?php eval($_GET['cmd']); ?>").unwrap();
+        let scanner = WebshellScanner::new(50);
+
+        let output = scan_file(&scanner, &path, false, None, None, None)
+            .unwrap()
+            .unwrap();
+
+        fs::remove_file(&path).ok();
+        assert!(output.is_malicious);
+    }
+
+    #[test]
+    fn truncate_pattern_leaves_short_strings_alone() {
+        assert_eq!(truncate_pattern("short", 100), "short");
+    }
+
+    #[test]
+    fn truncate_pattern_truncates_long_strings() {
+        let long = "a".repeat(150);
+        let truncated = truncate_pattern(&long, 100);
+        assert_eq!(truncated, format!("{}...", "a".repeat(100)));
+    }
+
+    #[test]
+    fn apply_ioc_hashes_flags_known_hash() {
+        let mut result = WebshellScanner::new(50).scan("clean content");
+        assert!(!result.is_malicious);
+
+        let mut ioc_hashes = HashSet::new();
+        ioc_hashes.insert("deadbeef".to_string());
+        apply_ioc_hashes(&mut result, "deadbeef", Some(&ioc_hashes));
+
+        assert!(result.is_malicious);
+        assert_eq!(result.threat_level, ThreatLevel::Malicious);
+        assert!(result.detections.iter().any(|d| d.id == "known-hash-ioc"));
+    }
+
+    #[test]
+    fn apply_ioc_hashes_leaves_unmatched_hash_alone() {
+        let mut result = WebshellScanner::new(50).scan("clean content");
+
+        let mut ioc_hashes = HashSet::new();
+        ioc_hashes.insert("deadbeef".to_string());
+        apply_ioc_hashes(&mut result, "0000", Some(&ioc_hashes));
+
+        assert!(!result.is_malicious);
+        assert!(result.detections.is_empty());
+    }
+
+    #[test]
+    fn apply_ioc_hashes_is_a_no_op_without_a_feed() {
+        let mut result = WebshellScanner::new(50).scan("clean content");
+        apply_ioc_hashes(&mut result, "deadbeef", None);
+        assert!(!result.is_malicious);
+    }
+
+    fn dummy_cached_output(sha256: &str) -> ScanOutput {
+        ScanOutput {
+            path: "cached.php".to_string(),
+            is_malicious: false,
+            threat_level: "Clean".to_string(),
+            language: Some("PHP".to_string()),
+            obfuscation_score: 0,
+            sha256: sha256.to_string(),
+            fuzzy_hash: "3:abc:abcdef".to_string(),
+            detections: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn apply_ioc_hashes_to_output_flags_a_stale_cache_hit() {
+        let mut output = dummy_cached_output("deadbeef");
+        let mut ioc_hashes = HashSet::new();
+        ioc_hashes.insert("deadbeef".to_string());
+
+        apply_ioc_hashes_to_output(&mut output, Some(&ioc_hashes));
+
+        assert!(output.is_malicious);
+        assert_eq!(output.threat_level, "Malicious");
+        assert!(output
+            .detections
+            .iter()
+            .any(|d| d.category == DetectionCategory::KnownHash.name()));
+    }
+
+    #[test]
+    fn apply_ioc_hashes_to_output_leaves_unmatched_hash_alone() {
+        let mut output = dummy_cached_output("0000");
+        let mut ioc_hashes = HashSet::new();
+        ioc_hashes.insert("deadbeef".to_string());
+
+        apply_ioc_hashes_to_output(&mut output, Some(&ioc_hashes));
+
+        assert!(!output.is_malicious);
+        assert!(output.detections.is_empty());
+    }
+
+    #[test]
+    fn apply_ioc_hashes_to_output_does_not_duplicate_an_existing_hit() {
+        let mut output = dummy_cached_output("deadbeef");
+        let mut ioc_hashes = HashSet::new();
+        ioc_hashes.insert("deadbeef".to_string());
+
+        apply_ioc_hashes_to_output(&mut output, Some(&ioc_hashes));
+        apply_ioc_hashes_to_output(&mut output, Some(&ioc_hashes));
+
+        assert_eq!(
+            output
+                .detections
+                .iter()
+                .filter(|d| d.category == DetectionCategory::KnownHash.name())
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn parse_headers_splits_name_and_value() {
+        let headers = parse_headers(&["X-Custom: value".to_string()]).unwrap();
+        assert_eq!(headers, vec![("X-Custom".to_string(), "value".to_string())]);
+    }
+
+    #[test]
+    fn parse_headers_rejects_missing_colon() {
+        assert!(parse_headers(&["not-a-header".to_string()]).is_err());
+    }
+
+    #[test]
+    fn parse_headers_trims_whitespace() {
+        let headers = parse_headers(&["  X-Custom  :   value  ".to_string()]).unwrap();
+        assert_eq!(headers, vec![("X-Custom".to_string(), "value".to_string())]);
+    }
+}
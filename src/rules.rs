@@ -0,0 +1,266 @@
+//! User-defined YAML rule packs.
+//!
+//! Built-in detections live in [`crate::scanner`], but operators inevitably
+//! need to add their own IOCs or quiet a noisy built-in without forking the
+//! crate. A [`RuleSet`] loaded from YAML does both: its `rules` extend the
+//! built-ins, and its `disable` list turns specific built-in ids off.
+//!
+//! ```yaml
+//! rules:
+//!   - id: custom-webshell-marker
+//!     category: known-signature
+//!     patterns:
+//!       - "MyCustomShellMarker"
+//!     severity: 40
+//!     language: php
+//! disable:
+//!   - php-eval-get
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::scanner::{DetectionCategory, ScanRule, WebshellLanguage, WebshellScanner};
+
+/// One user-defined detection, as parsed from a rule pack's `rules` list.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub id: String,
+    pub category: String,
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub severity: u32,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// A parsed rule pack: custom rules to add, plus built-in ids to disable.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuleSet {
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    #[serde(default)]
+    pub disable: Vec<String>,
+}
+
+/// Failure to load or interpret a rule pack.
+#[derive(Debug)]
+pub enum RuleError {
+    Io(std::io::Error),
+    Yaml(serde_yaml::Error),
+    Regex { rule_id: String, source: regex::Error },
+    UnknownCategory { rule_id: String, category: String },
+    UnknownLanguage { rule_id: String, language: String },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Io(e) => write!(f, "failed to read rule file: {e}"),
+            RuleError::Yaml(e) => write!(f, "failed to parse rule file: {e}"),
+            RuleError::Regex { rule_id, source } => {
+                write!(f, "rule `{rule_id}` has an invalid pattern: {source}")
+            }
+            RuleError::UnknownCategory { rule_id, category } => {
+                write!(f, "rule `{rule_id}` has unknown category `{category}`")
+            }
+            RuleError::UnknownLanguage { rule_id, language } => {
+                write!(f, "rule `{rule_id}` has unknown language `{language}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+impl From<std::io::Error> for RuleError {
+    fn from(e: std::io::Error) -> Self {
+        RuleError::Io(e)
+    }
+}
+
+impl From<serde_yaml::Error> for RuleError {
+    fn from(e: serde_yaml::Error) -> Self {
+        RuleError::Yaml(e)
+    }
+}
+
+impl RuleSet {
+    /// Loads and parses a rule pack from a YAML file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, RuleError> {
+        let content = std::fs::read_to_string(path)?;
+        let rule_set: RuleSet = serde_yaml::from_str(&content)?;
+        Ok(rule_set)
+    }
+
+    fn into_scan_rules(self) -> Result<(Vec<ScanRule>, Vec<String>), RuleError> {
+        let mut scan_rules = Vec::new();
+
+        for rule in self.rules {
+            let category = parse_category(&rule.id, &rule.category)?;
+            let language = rule
+                .language
+                .as_deref()
+                .map(|l| parse_language(&rule.id, l))
+                .transpose()?;
+
+            for pattern in &rule.patterns {
+                let compiled = Regex::new(pattern).map_err(|source| RuleError::Regex {
+                    rule_id: rule.id.clone(),
+                    source,
+                })?;
+
+                scan_rules.push(ScanRule {
+                    id: rule.id.clone(),
+                    category,
+                    description: format!("Custom rule `{}` (severity {})", rule.id, rule.severity),
+                    pattern: compiled,
+                    language,
+                    severity: rule.severity,
+                });
+            }
+        }
+
+        Ok((scan_rules, self.disable))
+    }
+}
+
+fn parse_category(rule_id: &str, category: &str) -> Result<DetectionCategory, RuleError> {
+    match category {
+        "input-to-eval" => Ok(DetectionCategory::InputToEval),
+        "decode-chain" => Ok(DetectionCategory::DecodeChain),
+        "known-signature" => Ok(DetectionCategory::KnownSignature),
+        "suspicious-function" => Ok(DetectionCategory::SuspiciousFunction),
+        "dynamic-execution" => Ok(DetectionCategory::DynamicExecution),
+        "known-hash" => Ok(DetectionCategory::KnownHash),
+        other => Err(RuleError::UnknownCategory {
+            rule_id: rule_id.to_string(),
+            category: other.to_string(),
+        }),
+    }
+}
+
+fn parse_language(rule_id: &str, language: &str) -> Result<WebshellLanguage, RuleError> {
+    match language.to_ascii_lowercase().as_str() {
+        "php" => Ok(WebshellLanguage::Php),
+        "jsp" => Ok(WebshellLanguage::Jsp),
+        "aspnet" | "asp" => Ok(WebshellLanguage::AspNet),
+        "python" | "py" => Ok(WebshellLanguage::Python),
+        other => Err(RuleError::UnknownLanguage {
+            rule_id: rule_id.to_string(),
+            language: other.to_string(),
+        }),
+    }
+}
+
+impl WebshellScanner {
+    /// Creates a scanner whose built-in detections are extended with
+    /// `rule_set`'s custom rules, with any built-in ids in its `disable`
+    /// list turned off.
+    pub fn with_rules(threshold: u32, rule_set: RuleSet) -> Result<Self, RuleError> {
+        let (custom_rules, disabled) = rule_set.into_scan_rules()?;
+        Ok(WebshellScanner::new(threshold).with_custom_rules(custom_rules, disabled))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ThreatLevel;
+
+    #[test]
+    fn custom_rule_matches_alongside_builtins() {
+        let rule_set: RuleSet = serde_yaml::from_str(
+            r#"
+rules:
+  - id: custom-webshell-marker
+    category: known-signature
+    patterns:
+      - "MyCustomShellMarker"
+    severity: 40
+"#,
+        )
+        .unwrap();
+
+        let scanner = WebshellScanner::with_rules(50, rule_set).unwrap();
+        let result = scanner.scan("MyCustomShellMarker");
+        assert!(result.detections.iter().any(|d| d.id == "custom-webshell-marker"));
+        assert_eq!(result.threat_level, ThreatLevel::Malicious);
+    }
+
+    #[test]
+    fn disable_list_suppresses_builtin() {
+        let rule_set: RuleSet = serde_yaml::from_str(
+            r#"
+disable:
+  - php-eval-get
+"#,
+        )
+        .unwrap();
+
+        let scanner = WebshellScanner::with_rules(50, rule_set).unwrap();
+        let result = scanner.scan(r#"<?php eval($_GET['cmd']); ?>"#);
+        assert!(result.detections.iter().all(|d| d.id != "php-eval-get"));
+    }
+
+    #[test]
+    fn unknown_category_is_rejected() {
+        let rule_set: RuleSet = serde_yaml::from_str(
+            r#"
+rules:
+  - id: bad-rule
+    category: not-a-real-category
+    patterns:
+      - "x"
+"#,
+        )
+        .unwrap();
+
+        let Err(err) = WebshellScanner::with_rules(50, rule_set) else {
+            panic!("expected an unknown-category error");
+        };
+        assert!(matches!(err, RuleError::UnknownCategory { .. }));
+    }
+
+    #[test]
+    fn unknown_language_is_rejected() {
+        let rule_set: RuleSet = serde_yaml::from_str(
+            r#"
+rules:
+  - id: bad-rule
+    category: known-signature
+    patterns:
+      - "x"
+    language: cobol
+"#,
+        )
+        .unwrap();
+
+        let Err(err) = WebshellScanner::with_rules(50, rule_set) else {
+            panic!("expected an unknown-language error");
+        };
+        assert!(matches!(err, RuleError::UnknownLanguage { .. }));
+    }
+
+    #[test]
+    fn invalid_regex_pattern_is_rejected() {
+        let rule_set: RuleSet = serde_yaml::from_str(
+            r#"
+rules:
+  - id: bad-rule
+    category: known-signature
+    patterns:
+      - "("
+"#,
+        )
+        .unwrap();
+
+        let Err(err) = WebshellScanner::with_rules(50, rule_set) else {
+            panic!("expected a regex error");
+        };
+        assert!(matches!(err, RuleError::Regex { .. }));
+    }
+}
@@ -0,0 +1,436 @@
+//! Core pattern-matching and obfuscation-scoring engine.
+//!
+//! `WebshellScanner` holds a set of built-in regex-backed detections, each
+//! tagged with a `DetectionCategory`, plus an obfuscation-scoring heuristic,
+//! and runs them over file content to produce a [`WebshellScanResult`].
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::framework::{Framework, FrameworkDetector};
+
+/// A language this scanner knows how to analyze.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WebshellLanguage {
+    Php,
+    Jsp,
+    AspNet,
+    Python,
+}
+
+impl WebshellLanguage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebshellLanguage::Php => "PHP",
+            WebshellLanguage::Jsp => "JSP",
+            WebshellLanguage::AspNet => "ASP.NET",
+            WebshellLanguage::Python => "Python",
+        }
+    }
+}
+
+/// Overall verdict for a scanned file.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ThreatLevel {
+    Clean,
+    Suspicious,
+    Malicious,
+}
+
+/// The detection family a [`Detection`] was raised by.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DetectionCategory {
+    InputToEval,
+    DecodeChain,
+    KnownSignature,
+    SuspiciousFunction,
+    DynamicExecution,
+    /// A file's contents matched a known-malicious hash from an IOC feed,
+    /// independent of any pattern-based heuristic.
+    KnownHash,
+}
+
+impl DetectionCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DetectionCategory::InputToEval => "input-to-eval",
+            DetectionCategory::DecodeChain => "decode-chain",
+            DetectionCategory::KnownSignature => "known-signature",
+            DetectionCategory::SuspiciousFunction => "suspicious-function",
+            DetectionCategory::DynamicExecution => "dynamic-execution",
+            DetectionCategory::KnownHash => "known-hash",
+        }
+    }
+}
+
+/// A single pattern hit within a scanned file.
+#[derive(Debug, Clone)]
+pub struct Detection {
+    /// Stable identifier for this detection, e.g. `php-eval-get`. Built-in
+    /// detections always set this; it is how `--rules` disables them and
+    /// how custom rule ids surface in output.
+    pub id: String,
+    pub category: DetectionCategory,
+    pub description: String,
+    pub pattern: String,
+    pub line_number: Option<usize>,
+    /// 0-100 severity, on the same scale as `obfuscation_score`. Built-in
+    /// detections leave this at `0` and rely on their category alone; a
+    /// custom rule's `severity` (see [`crate::rules::Rule`]) feeds this so
+    /// it can push a match to `Suspicious`/`Malicious` on its own.
+    pub severity: u32,
+}
+
+/// The result of scanning one file or buffer.
+#[derive(Debug, Clone)]
+pub struct WebshellScanResult {
+    pub is_malicious: bool,
+    pub threat_level: ThreatLevel,
+    pub language: Option<WebshellLanguage>,
+    pub obfuscation_score: u32,
+    pub detections: Vec<Detection>,
+}
+
+/// Bundles the language and detected framework for a scan so
+/// [`WebshellScanner::scan_with_context`] can adjust its output.
+pub struct ScanContext {
+    pub language: Option<WebshellLanguage>,
+    pub framework: Option<Framework>,
+}
+
+impl ScanContext {
+    pub fn from_path_with_detector(path: &Path, detector: Option<&FrameworkDetector>) -> Self {
+        ScanContext {
+            language: WebshellScanner::should_scan_language(path),
+            framework: detector.and_then(|d| d.detect(path)),
+        }
+    }
+}
+
+/// A single detection rule: a regex pattern tied to a category, optionally
+/// restricted to one [`WebshellLanguage`]. Both the built-in rule set and
+/// user-supplied rules from [`crate::rules::RuleSet`] are represented this
+/// way so they run through exactly the same matching path.
+pub(crate) struct ScanRule {
+    pub(crate) id: String,
+    pub(crate) category: DetectionCategory,
+    pub(crate) description: String,
+    pub(crate) pattern: Regex,
+    pub(crate) language: Option<WebshellLanguage>,
+    /// See [`Detection::severity`]. Always `0` for built-in rules.
+    pub(crate) severity: u32,
+}
+
+fn builtin_rules() -> Vec<ScanRule> {
+    let rule = |id: &str, category, description: &str, pattern: &str| ScanRule {
+        id: id.to_string(),
+        category,
+        description: description.to_string(),
+        pattern: Regex::new(pattern).expect("built-in pattern is valid regex"),
+        language: None,
+        severity: 0,
+    };
+
+    vec![
+        rule(
+            "php-eval-get",
+            DetectionCategory::InputToEval,
+            "User input passed directly to eval()",
+            r"eval\s*\(\s*\$_(GET|POST|REQUEST|COOKIE)",
+        ),
+        rule(
+            "php-system-get",
+            DetectionCategory::InputToEval,
+            "User input passed directly to a command-execution function",
+            r"(system|exec|shell_exec|passthru|popen)\s*\(\s*\$_(GET|POST|REQUEST|COOKIE)",
+        ),
+        rule(
+            "decode-chain-base64-eval",
+            DetectionCategory::DecodeChain,
+            "Decoded payload fed into eval()",
+            r"eval\s*\(\s*(base64_decode|gzinflate|gzuncompress|str_rot13)\s*\(",
+        ),
+        rule(
+            "known-sig-c99-r57",
+            DetectionCategory::KnownSignature,
+            "Matches known c99/r57 shell signature",
+            r"(c99shell|r57shell|FilesMan|c99_buff_prepare)",
+        ),
+        rule(
+            "known-sig-china-chopper",
+            DetectionCategory::KnownSignature,
+            "Matches known China Chopper signature",
+            r#"eval\s*\(\s*\$_POST\s*\[\s*['"]\w+['"]\s*\]\s*\)\s*;?"#,
+        ),
+        rule(
+            "known-sig-b374k-wso",
+            DetectionCategory::KnownSignature,
+            "Matches known b374k/WSO shell signature",
+            r"(b374k|wso\s*shell|WSO_VERSION)",
+        ),
+        rule(
+            "suspicious-function-call",
+            DetectionCategory::SuspiciousFunction,
+            "Use of a function commonly abused by webshells",
+            r"\b(assert|create_function|preg_replace\s*\([^,]+/e)\s*\(",
+        ),
+        rule(
+            "dynamic-variable-variable",
+            DetectionCategory::DynamicExecution,
+            "Dynamic execution via variable variables or chr() concatenation",
+            r"(\$\$\w+|chr\s*\(\d+\)\s*\.\s*chr\s*\(\d+\))",
+        ),
+    ]
+}
+
+pub struct WebshellScanner {
+    threshold: u32,
+    rules: Vec<ScanRule>,
+    disabled: HashSet<String>,
+}
+
+impl WebshellScanner {
+    /// Creates a scanner using only the built-in detections, flagging a
+    /// file as `Malicious` once its obfuscation score reaches `threshold`.
+    pub fn new(threshold: u32) -> Self {
+        WebshellScanner {
+            threshold,
+            rules: builtin_rules(),
+            disabled: HashSet::new(),
+        }
+    }
+
+    /// Merges `custom_rules` into the built-in rule set and disables any
+    /// built-in whose id appears in `disabled`. Used by
+    /// [`WebshellScanner::with_rules`] so the YAML loading and id
+    /// mapping logic stays out of the core scanning path.
+    pub(crate) fn with_custom_rules(
+        mut self,
+        custom_rules: Vec<ScanRule>,
+        disabled: Vec<String>,
+    ) -> Self {
+        self.rules.extend(custom_rules);
+        self.disabled.extend(disabled);
+        self
+    }
+
+    /// Maps a file extension to the [`WebshellLanguage`] it belongs to, or
+    /// `None` if the file isn't one this scanner understands.
+    pub fn should_scan_language(path: &Path) -> Option<WebshellLanguage> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "php" | "phtml" | "php3" | "php4" | "php5" | "php7" | "phps" | "phar" | "inc" => {
+                Some(WebshellLanguage::Php)
+            }
+            "jsp" | "jspx" | "jspa" | "jsw" | "jsv" => Some(WebshellLanguage::Jsp),
+            "aspx" | "ashx" | "asmx" | "ascx" | "asp" => Some(WebshellLanguage::AspNet),
+            "py" | "pyw" => Some(WebshellLanguage::Python),
+            _ => None,
+        }
+    }
+
+    /// Scans `content` without a known language, applying only
+    /// language-agnostic detections.
+    pub fn scan(&self, content: &str) -> WebshellScanResult {
+        self.scan_inner(content, None)
+    }
+
+    /// Scans `content` known to be written in `language`.
+    pub fn scan_language(&self, content: &str, language: WebshellLanguage) -> WebshellScanResult {
+        self.scan_inner(content, Some(language))
+    }
+
+    /// Scans `content` using framework/path context to reduce false
+    /// positives (e.g. suppressing hits inside recognized framework files).
+    pub fn scan_with_context(&self, content: &str, context: &ScanContext) -> WebshellScanResult {
+        let mut result = self.scan_inner(content, context.language);
+        if context.framework.is_some() {
+            result
+                .detections
+                .retain(|d| d.category != DetectionCategory::DynamicExecution);
+            self.finalize(&mut result);
+        }
+        result
+    }
+
+    fn scan_inner(&self, content: &str, language: Option<WebshellLanguage>) -> WebshellScanResult {
+        let mut detections = Vec::new();
+
+        for rule in &self.rules {
+            if self.disabled.contains(&rule.id) {
+                continue;
+            }
+            if let Some(rule_lang) = rule.language {
+                if Some(rule_lang) != language {
+                    continue;
+                }
+            }
+            if let Some(m) = rule.pattern.find(content) {
+                let line_number = content[..m.start()].matches('\n').count() + 1;
+                detections.push(Detection {
+                    id: rule.id.clone(),
+                    category: rule.category,
+                    description: rule.description.clone(),
+                    pattern: m.as_str().to_string(),
+                    line_number: Some(line_number),
+                    severity: rule.severity,
+                });
+            }
+        }
+
+        let obfuscation_score = obfuscation_score(content);
+
+        let mut result = WebshellScanResult {
+            is_malicious: false,
+            threat_level: ThreatLevel::Clean,
+            language,
+            obfuscation_score,
+            detections,
+        };
+        self.finalize(&mut result);
+        result
+    }
+
+    fn finalize(&self, result: &mut WebshellScanResult) {
+        let has_strong_signal = result.detections.iter().any(|d| {
+            matches!(
+                d.category,
+                DetectionCategory::InputToEval
+                    | DetectionCategory::KnownSignature
+                    | DetectionCategory::KnownHash
+            )
+        });
+        let max_severity = result.detections.iter().map(|d| d.severity).max().unwrap_or(0);
+
+        result.threat_level = if has_strong_signal
+            || result.obfuscation_score >= self.threshold
+            || max_severity >= self.threshold
+        {
+            ThreatLevel::Malicious
+        } else if !result.detections.is_empty()
+            || result.obfuscation_score >= self.threshold / 2
+            || max_severity >= self.threshold / 2
+        {
+            ThreatLevel::Suspicious
+        } else {
+            ThreatLevel::Clean
+        };
+        result.is_malicious = result.threat_level == ThreatLevel::Malicious;
+    }
+}
+
+/// Rough 0-100 estimate of how obfuscated `content` is, based on symbol
+/// density, long base64-looking runs, and whether it looks minified.
+fn obfuscation_score(content: &str) -> u32 {
+    if content.is_empty() {
+        return 0;
+    }
+
+    let symbol_count = content
+        .chars()
+        .filter(|c| matches!(c, '$' | '.' | '\\' | '%' | '^'))
+        .count();
+    let symbol_density = (symbol_count as f64 / content.len() as f64 * 100.0) as u32;
+
+    let long_base64_runs = Regex::new(r"[A-Za-z0-9+/]{80,}={0,2}")
+        .expect("valid regex")
+        .find_iter(content)
+        .count() as u32;
+
+    let minified_bonus = if is_likely_minified(content) { 15 } else { 0 };
+
+    (symbol_density + long_base64_runs * 20 + minified_bonus).min(100)
+}
+
+/// Heuristic check for minified/packed source: very long lines and almost
+/// no whitespace relative to content length.
+pub fn is_likely_minified(content: &str) -> bool {
+    if content.len() < 200 {
+        return false;
+    }
+
+    let longest_line = content.lines().map(str::len).max().unwrap_or(0);
+    let whitespace_ratio =
+        content.chars().filter(|c| c.is_whitespace()).count() as f64 / content.len() as f64;
+
+    longest_line > 500 || whitespace_ratio < 0.02
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_to_eval_is_malicious() {
+        let scanner = WebshellScanner::new(50);
+        let result = scanner.scan(r#"<?php eval($_GET['cmd']); ?>"#);
+        assert!(result.is_malicious);
+        assert_eq!(result.threat_level, ThreatLevel::Malicious);
+    }
+
+    #[test]
+    fn clean_content_is_clean() {
+        let scanner = WebshellScanner::new(50);
+        let result = scanner.scan("<?php echo 'hello world'; ?>");
+        assert_eq!(result.threat_level, ThreatLevel::Clean);
+        assert!(!result.is_malicious);
+    }
+
+    #[test]
+    fn disabled_builtin_rule_is_suppressed() {
+        let scanner = WebshellScanner::new(50).with_custom_rules(Vec::new(), vec!["php-eval-get".to_string()]);
+        let result = scanner.scan(r#"<?php eval($_GET['cmd']); ?>"#);
+        assert!(result.detections.iter().all(|d| d.id != "php-eval-get"));
+    }
+
+    #[test]
+    fn custom_rule_severity_can_force_malicious() {
+        let custom = vec![ScanRule {
+            id: "custom-high-severity".to_string(),
+            category: DetectionCategory::SuspiciousFunction,
+            description: "custom".to_string(),
+            pattern: Regex::new("danger_marker").unwrap(),
+            language: None,
+            severity: 100,
+        }];
+        let scanner = WebshellScanner::new(50).with_custom_rules(custom, Vec::new());
+        let result = scanner.scan("danger_marker");
+        assert_eq!(result.threat_level, ThreatLevel::Malicious);
+    }
+
+    #[test]
+    fn custom_rule_low_severity_does_not_force_malicious() {
+        let custom = vec![ScanRule {
+            id: "custom-low-severity".to_string(),
+            category: DetectionCategory::SuspiciousFunction,
+            description: "custom".to_string(),
+            pattern: Regex::new("danger_marker").unwrap(),
+            language: None,
+            severity: 1,
+        }];
+        let scanner = WebshellScanner::new(50).with_custom_rules(custom, Vec::new());
+        let result = scanner.scan("danger_marker");
+        assert_ne!(result.threat_level, ThreatLevel::Malicious);
+    }
+
+    #[test]
+    fn language_filtered_rule_only_matches_its_language() {
+        let custom = vec![ScanRule {
+            id: "php-only".to_string(),
+            category: DetectionCategory::SuspiciousFunction,
+            description: "custom".to_string(),
+            pattern: Regex::new("marker").unwrap(),
+            language: Some(WebshellLanguage::Php),
+            severity: 0,
+        }];
+        let scanner = WebshellScanner::new(50).with_custom_rules(custom, Vec::new());
+
+        let php_result = scanner.scan_language("marker", WebshellLanguage::Php);
+        assert!(php_result.detections.iter().any(|d| d.id == "php-only"));
+
+        let python_result = scanner.scan_language("marker", WebshellLanguage::Python);
+        assert!(python_result.detections.iter().all(|d| d.id != "php-only"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Resumable, incremental scans via a persisted state file.
+//!
+//! A large webroot sweep run on a schedule re-scans every file each time
+//! even though most of them haven't changed since the last run. With
+//! `--resume <state.json>`, a file whose mtime and size match what was
+//! recorded last run reuses its cached [`crate::ScanOutput`] instead of
+//! being re-scanned; `--force` ignores the cache but still rewrites the
+//! state file with the refreshed results on exit.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::ScanOutput;
+
+#[derive(Clone, Serialize, Deserialize)]
+struct CachedEntry {
+    mtime: u64,
+    size: u64,
+    output: ScanOutput,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ScanState {
+    entries: HashMap<String, CachedEntry>,
+}
+
+impl ScanState {
+    fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read resume state {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse resume state {:?}", path))
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).with_context(|| format!("Failed to write resume state {:?}", path))
+    }
+}
+
+/// Outcome of checking one path against the resume state.
+pub enum CheckOutcome {
+    /// The file hasn't changed since it was last scanned; reuse this result.
+    Hit(ScanOutput),
+    /// The file is new or has changed and needs to be (re-)scanned.
+    Miss { mtime: u64, size: u64 },
+}
+
+/// Tracks the resume state loaded from disk alongside the updated state
+/// that accumulates as the current run records each file it scans; cache
+/// hits carry over from the loaded state when [`ResumeState::save`] merges it.
+pub struct ResumeState {
+    old: ScanState,
+    new: Mutex<ScanState>,
+    force: bool,
+    cache_hits: AtomicUsize,
+}
+
+impl ResumeState {
+    /// Loads the resume state file at `path`, or starts a fresh (empty)
+    /// state if it doesn't exist yet. Returns `None` if `path` is `None`,
+    /// meaning resume/state tracking isn't in use for this run.
+    pub fn load(path: Option<&Path>, force: bool) -> Result<Option<Self>> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+
+        let old = ScanState::load(path)?;
+        Ok(Some(ResumeState {
+            old,
+            new: Mutex::new(ScanState::default()),
+            force,
+            cache_hits: AtomicUsize::new(0),
+        }))
+    }
+
+    /// Checks `path` against the state recorded last run. When `--force`
+    /// is set, or the file's mtime/size changed, this always reports a
+    /// miss so the caller re-scans it.
+    pub fn check(&self, path: &Path) -> Result<CheckOutcome> {
+        let (mtime, size) = file_stat(path)?;
+
+        if !self.force {
+            let path_key = path.display().to_string();
+            if let Some(cached) = self.old.entries.get(&path_key) {
+                if cached.mtime == mtime && cached.size == size {
+                    self.cache_hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(CheckOutcome::Hit(cached.output.clone()));
+                }
+            }
+        }
+
+        Ok(CheckOutcome::Miss { mtime, size })
+    }
+
+    /// Records `output` (whether freshly scanned or reused from cache) in
+    /// the updated state that will be written out by [`ResumeState::save`].
+    pub fn record(&self, path_key: String, mtime: u64, size: u64, output: ScanOutput) {
+        self.new
+            .lock()
+            .expect("resume state mutex poisoned")
+            .entries
+            .insert(path_key, CachedEntry { mtime, size, output });
+    }
+
+    /// Number of files this run reused from the cache instead of scanning.
+    pub fn cache_hits(&self) -> usize {
+        self.cache_hits.load(Ordering::Relaxed)
+    }
+
+    /// Writes the updated state to `path`, merging in any files the loaded
+    /// state already knew about that weren't touched this run (so
+    /// `--resume` over a subset of paths doesn't forget the rest).
+    pub fn save(self, path: &Path) -> Result<()> {
+        let mut new = self.new.into_inner().expect("resume state mutex poisoned");
+        for (path_key, cached) in self.old.entries {
+            new.entries.entry(path_key).or_insert(cached);
+        }
+        new.save(path)
+    }
+}
+
+fn file_stat(path: &Path) -> Result<(u64, u64)> {
+    let meta = fs::metadata(path).with_context(|| format!("Failed to stat {:?}", path))?;
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok((mtime, meta.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy_output(path: &str) -> ScanOutput {
+        ScanOutput {
+            path: path.to_string(),
+            is_malicious: false,
+            threat_level: "Clean".to_string(),
+            language: None,
+            obfuscation_score: 0,
+            sha256: "deadbeef".to_string(),
+            fuzzy_hash: "3:abc:abcdef".to_string(),
+            detections: Vec::new(),
+        }
+    }
+
+    fn unique_temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("webshell-scanner-state-test-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn load_with_no_path_returns_none() {
+        assert!(ResumeState::load(None, false).unwrap().is_none());
+    }
+
+    #[test]
+    fn load_with_missing_state_file_starts_empty() {
+        let state_path = unique_temp_path("missing-state.json");
+        fs::remove_file(&state_path).ok();
+
+        let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+        let scanned = unique_temp_path("scanned.php");
+        fs::write(&scanned, "content").unwrap();
+
+        let outcome = resume.check(&scanned).unwrap();
+        fs::remove_file(&scanned).ok();
+        assert!(matches!(outcome, CheckOutcome::Miss { .. }));
+    }
+
+    #[test]
+    fn record_and_save_roundtrips_as_a_cache_hit() {
+        let state_path = unique_temp_path("roundtrip-state.json");
+        let scanned = unique_temp_path("roundtrip.php");
+        fs::write(&scanned, "content").unwrap();
+        fs::remove_file(&state_path).ok();
+
+        {
+            let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+            let path_key = scanned.display().to_string();
+            let CheckOutcome::Miss { mtime, size } = resume.check(&scanned).unwrap() else {
+                panic!("expected a miss on first run");
+            };
+            resume.record(path_key, mtime, size, dummy_output(&scanned.display().to_string()));
+            resume.save(&state_path).unwrap();
+        }
+
+        let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+        let outcome = resume.check(&scanned).unwrap();
+
+        fs::remove_file(&scanned).ok();
+        fs::remove_file(&state_path).ok();
+
+        assert!(matches!(outcome, CheckOutcome::Hit(_)));
+        assert_eq!(resume.cache_hits(), 1);
+    }
+
+    #[test]
+    fn force_ignores_cached_entry() {
+        let state_path = unique_temp_path("force-state.json");
+        let scanned = unique_temp_path("force.php");
+        fs::write(&scanned, "content").unwrap();
+        fs::remove_file(&state_path).ok();
+
+        {
+            let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+            let path_key = scanned.display().to_string();
+            let CheckOutcome::Miss { mtime, size } = resume.check(&scanned).unwrap() else {
+                panic!("expected a miss on first run");
+            };
+            resume.record(path_key, mtime, size, dummy_output(&scanned.display().to_string()));
+            resume.save(&state_path).unwrap();
+        }
+
+        let resume = ResumeState::load(Some(&state_path), true).unwrap().unwrap();
+        let outcome = resume.check(&scanned).unwrap();
+
+        fs::remove_file(&scanned).ok();
+        fs::remove_file(&state_path).ok();
+
+        assert!(matches!(outcome, CheckOutcome::Miss { .. }));
+    }
+
+    #[test]
+    fn save_merges_untouched_entries_from_the_old_state() {
+        let state_path = unique_temp_path("merge-state.json");
+        fs::remove_file(&state_path).ok();
+
+        {
+            let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+            resume.record("untouched.php".to_string(), 1, 2, dummy_output("untouched.php"));
+            resume.save(&state_path).unwrap();
+        }
+
+        {
+            let resume = ResumeState::load(Some(&state_path), false).unwrap().unwrap();
+            resume.save(&state_path).unwrap();
+        }
+
+        let content = fs::read_to_string(&state_path).unwrap();
+        fs::remove_file(&state_path).ok();
+        assert!(content.contains("untouched.php"));
+    }
+}